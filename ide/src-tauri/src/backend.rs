@@ -0,0 +1,221 @@
+//! Pluggable execution backends.
+//!
+//! Every command used to shell out to granitectl directly. `Backend` abstracts that
+//! down to the four operations the frontend actually needs (`exec`, `explain`,
+//! `metadata`, `export`), so a session can be backed by either the local granitectl
+//! binary or a remote Postgres-style server without the frontend's JSON contract
+//! changing. `resolve` picks the implementation from the connection string: a
+//! `postgres://`/`http(s)://` URL goes to `RemoteBackend`, anything else is treated
+//! as a local database file handled by `GranitectlBackend`.
+
+use std::path::Path;
+
+use crate::QueryResultPayload;
+
+pub enum ExecOutput {
+    Json(QueryResultPayload),
+    Raw(String),
+}
+
+pub trait Backend {
+    fn exec(&self, sql: &str, format: &str) -> Result<ExecOutput, String>;
+    fn explain(&self, sql: &str) -> Result<String, String>;
+    fn metadata(&self) -> Result<String, String>;
+    fn export(&self, sql: &str, format: &str) -> Result<String, String>;
+}
+
+pub fn is_remote(connection: &str) -> bool {
+    connection.starts_with("postgres://")
+        || connection.starts_with("postgresql://")
+        || connection.starts_with("http://")
+        || connection.starts_with("https://")
+}
+
+pub fn resolve(connection: &str) -> Result<Box<dyn Backend>, String> {
+    if is_remote(connection) {
+        Ok(Box::new(RemoteBackend::connect(connection)?))
+    } else {
+        Ok(Box::new(GranitectlBackend::open(connection)?))
+    }
+}
+
+struct GranitectlBackend {
+    db: String,
+    key_hex: Option<String>,
+}
+
+impl GranitectlBackend {
+    fn open(path: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Err("Database file not found".into());
+        }
+        Ok(GranitectlBackend {
+            db: path.to_string(),
+            key_hex: crate::crypto::key_hex_for(path),
+        })
+    }
+
+    /// Inserts `--key-hex` ahead of the trailing db-path argument when this
+    /// database was unlocked with a passphrase. Every granitectl call is a
+    /// fresh process, so there's no persistent connection to carry the
+    /// derived key on — it has to be re-supplied on each invocation.
+    fn with_key<'a>(&'a self, mut args: Vec<&'a str>) -> Vec<&'a str> {
+        if let Some(key_hex) = &self.key_hex {
+            let db_arg = args.pop();
+            args.push("--key-hex");
+            args.push(key_hex);
+            if let Some(db_arg) = db_arg {
+                args.push(db_arg);
+            }
+        }
+        args
+    }
+}
+
+impl Backend for GranitectlBackend {
+    fn exec(&self, sql: &str, format: &str) -> Result<ExecOutput, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        match format {
+            "jsonRows" => {
+                let args = self.with_key(vec!["exec", "--format", "json", "-q", sql, &self.db]);
+                match crate::run_granitectl(&args) {
+                    Ok(output) => {
+                        let payload: QueryResultPayload = serde_json::from_str(&output.stdout)
+                            .map_err(|err| format!("Failed to parse JSON output: {err}"))?;
+                        Ok(ExecOutput::Json(payload))
+                    }
+                    Err(err) => {
+                        if crate::is_unknown_format_error(&err) {
+                            Ok(ExecOutput::Json(crate::legacy_exec_result(&self.db, sql)?))
+                        } else {
+                            Err(err)
+                        }
+                    }
+                }
+            }
+            "table" | "csv" => {
+                let args = self.with_key(vec!["exec", "--format", format, "-q", sql, &self.db]);
+                let output = crate::run_granitectl(&args)?;
+                Ok(ExecOutput::Raw(output.stdout))
+            }
+            other => Err(format!("Unsupported format {other}")),
+        }
+    }
+
+    fn explain(&self, sql: &str) -> Result<String, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        let args = self.with_key(vec!["explain", "--json", "-q", sql, &self.db]);
+        let output = crate::run_granitectl_readonly(&args)?;
+        Ok(output.stdout)
+    }
+
+    fn metadata(&self) -> Result<String, String> {
+        let args = self.with_key(vec!["meta", "--json", &self.db]);
+        match crate::run_granitectl_readonly(&args) {
+            Ok(output) => {
+                if !crate::looks_like_json(&output.stdout) {
+                    let preview = output.stdout.trim();
+                    if preview.contains("unknown command") {
+                        return crate::legacy_metadata(&self.db);
+                    }
+                    let message = if preview.is_empty() {
+                        "granitectl returned no metadata".to_string()
+                    } else {
+                        format!("granitectl metadata output was not JSON: {preview}")
+                    };
+                    return Err(message);
+                }
+                Ok(output.stdout)
+            }
+            Err(err) => {
+                if err.contains("unknown command") {
+                    crate::legacy_metadata(&self.db)
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    fn export(&self, sql: &str, format: &str) -> Result<String, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        let args = self.with_key(vec!["exec", "--format", format, "-q", sql, &self.db]);
+        let output = crate::run_granitectl(&args)?;
+        Ok(output.stdout)
+    }
+}
+
+/// Talks to an external Postgres-style server over a small JSON-over-HTTP protocol
+/// (`POST /exec`, `POST /explain`, `GET /metadata`, `POST /export`), producing the
+/// same `QueryResultPayload`/metadata JSON shapes the legacy granitectl parsers emit.
+struct RemoteBackend {
+    base_url: String,
+}
+
+impl RemoteBackend {
+    fn connect(url: &str) -> Result<Self, String> {
+        let base_url = url.trim_end_matches('/').to_string();
+        ureq::get(&format!("{base_url}/health"))
+            .call()
+            .map_err(|err| format!("Failed to reach remote engine at {base_url}: {err}"))?;
+        Ok(RemoteBackend { base_url })
+    }
+}
+
+impl Backend for RemoteBackend {
+    fn exec(&self, sql: &str, format: &str) -> Result<ExecOutput, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        let response = ureq::post(&format!("{}/exec", self.base_url))
+            .send_json(serde_json::json!({ "sql": sql, "format": format }))
+            .map_err(|err| format!("Remote exec failed: {err}"))?;
+        if format == "jsonRows" {
+            let payload: QueryResultPayload = response
+                .into_json()
+                .map_err(|err| format!("Failed to parse remote response: {err}"))?;
+            Ok(ExecOutput::Json(payload))
+        } else {
+            let text = response
+                .into_string()
+                .map_err(|err| format!("Failed to read remote response: {err}"))?;
+            Ok(ExecOutput::Raw(text))
+        }
+    }
+
+    fn explain(&self, sql: &str) -> Result<String, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        ureq::post(&format!("{}/explain", self.base_url))
+            .send_json(serde_json::json!({ "sql": sql }))
+            .map_err(|err| format!("Remote explain failed: {err}"))?
+            .into_string()
+            .map_err(|err| format!("Failed to read remote response: {err}"))
+    }
+
+    fn metadata(&self) -> Result<String, String> {
+        ureq::get(&format!("{}/metadata", self.base_url))
+            .call()
+            .map_err(|err| format!("Remote metadata request failed: {err}"))?
+            .into_string()
+            .map_err(|err| format!("Failed to read remote response: {err}"))
+    }
+
+    fn export(&self, sql: &str, format: &str) -> Result<String, String> {
+        if sql.trim().is_empty() {
+            return Err("SQL must not be empty".into());
+        }
+        ureq::post(&format!("{}/export", self.base_url))
+            .send_json(serde_json::json!({ "sql": sql, "format": format }))
+            .map_err(|err| format!("Remote export failed: {err}"))?
+            .into_string()
+            .map_err(|err| format!("Failed to read remote response: {err}"))
+    }
+}
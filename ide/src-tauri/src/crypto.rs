@@ -0,0 +1,174 @@
+//! Passphrase-protected databases.
+//!
+//! `create_db`/`open_db` can be given a passphrase, which is run through Argon2id to
+//! derive the key granitectl uses to encrypt page contents. A small plaintext header
+//! is kept alongside the database file (`<db>.granite-key`) holding the salt, the
+//! Argon2 parameters, and a password hash used purely to verify the passphrase before
+//! we ever hand a (possibly wrong) key to the engine.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+
+const MEMORY_COST_KIB: u32 = 19_456; // ~19 MiB
+const TIME_COST: u32 = 2;
+const PARALLELISM: u32 = 1;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DbOpenError {
+    NotFound { message: String },
+    BadPassphrase { message: String },
+    Other { message: String },
+}
+
+impl DbOpenError {
+    pub fn other(message: impl Into<String>) -> Self {
+        DbOpenError::Other {
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DbKeyHeader {
+    salt: String,
+    verifier: String,
+    m_cost_kib: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+fn header_path(db_path: &Path) -> std::path::PathBuf {
+    let mut os_string = db_path.as_os_str().to_owned();
+    os_string.push(".granite-key");
+    std::path::PathBuf::from(os_string)
+}
+
+fn argon2_with_params(params: &DbKeyHeader) -> Result<Argon2<'static>, DbOpenError> {
+    let argon_params = Params::new(params.m_cost_kib, params.t_cost, params.p_cost, Some(KEY_LEN))
+        .map_err(|err| DbOpenError::other(format!("Invalid Argon2 parameters: {err}")))?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params))
+}
+
+/// Creates the sidecar key header for a newly encrypted database and returns the
+/// derived 32-byte key to hand off to granitectl.
+pub fn provision(db_path: &Path, passphrase: &str) -> Result<Vec<u8>, DbOpenError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let header = DbKeyHeader {
+        salt: salt.to_string(),
+        verifier: String::new(),
+        m_cost_kib: MEMORY_COST_KIB,
+        t_cost: TIME_COST,
+        p_cost: PARALLELISM,
+    };
+    let argon2 = argon2_with_params(&header)?;
+
+    let password_hash = argon2
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map_err(|err| DbOpenError::other(format!("Failed to derive passphrase key: {err}")))?;
+    let header = DbKeyHeader {
+        verifier: password_hash.to_string(),
+        ..header
+    };
+
+    let mut key = vec![0u8; KEY_LEN];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt.as_str().as_bytes(), &mut key)
+        .map_err(|err| DbOpenError::other(format!("Failed to derive passphrase key: {err}")))?;
+
+    let encoded = serde_json::to_string_pretty(&header)
+        .map_err(|err| DbOpenError::other(format!("Failed to encode key header: {err}")))?;
+    fs::write(header_path(db_path), encoded)
+        .map_err(|err| DbOpenError::other(format!("Failed to write key header: {err}")))?;
+
+    Ok(key)
+}
+
+/// Returns `Ok(Some(key))` if `db_path` is passphrase-protected and `passphrase`
+/// matches, `Ok(None)` if the database is not protected at all, or a
+/// `DbOpenError::BadPassphrase` if protected and the passphrase is wrong.
+pub fn unlock(db_path: &Path, passphrase: Option<&str>) -> Result<Option<Vec<u8>>, DbOpenError> {
+    let header_path = header_path(db_path);
+    if !header_path.exists() {
+        return Ok(None);
+    }
+
+    let Some(passphrase) = passphrase else {
+        return Err(DbOpenError::BadPassphrase {
+            message: "This database is passphrase-protected".to_string(),
+        });
+    };
+
+    let contents = fs::read_to_string(&header_path)
+        .map_err(|err| DbOpenError::other(format!("Failed to read key header: {err}")))?;
+    let header: DbKeyHeader = serde_json::from_str(&contents)
+        .map_err(|err| DbOpenError::other(format!("Failed to parse key header: {err}")))?;
+
+    let parsed_hash = PasswordHash::new(&header.verifier)
+        .map_err(|err| DbOpenError::other(format!("Corrupt key header: {err}")))?;
+    let argon2 = argon2_with_params(&header)?;
+    if argon2
+        .verify_password(passphrase.as_bytes(), &parsed_hash)
+        .is_err()
+    {
+        return Err(DbOpenError::BadPassphrase {
+            message: "Incorrect passphrase".to_string(),
+        });
+    }
+
+    let mut key = vec![0u8; KEY_LEN];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), header.salt.as_bytes(), &mut key)
+        .map_err(|err| DbOpenError::other(format!("Failed to derive passphrase key: {err}")))?;
+    Ok(Some(key))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+/// Remembers the key derived for a passphrase-protected database so later
+/// granitectl invocations against it can decrypt without re-prompting.
+///
+/// `open_db`/`create_db` only run once per session, but every SQL operation
+/// after that shells out to a brand new `granitectl` process — there's no
+/// persistent connection object to hang the key off, so this process-wide
+/// table keyed by db path stands in for one.
+static KEY_SESSIONS: OnceLock<Mutex<HashMap<String, Vec<u8>>>> = OnceLock::new();
+
+fn key_sessions() -> &'static Mutex<HashMap<String, Vec<u8>>> {
+    KEY_SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records the derived key for `db_path`, to be handed to every later
+/// granitectl call against that database via [`key_hex_for`].
+pub fn remember_key(db_path: &str, key: Vec<u8>) {
+    key_sessions().lock().unwrap().insert(db_path.to_string(), key);
+}
+
+/// Returns the hex-encoded key remembered for `db_path`, if `open_db`/`create_db`
+/// unlocked or provisioned one for it earlier in this session.
+pub fn key_hex_for(db_path: &str) -> Option<String> {
+    key_sessions()
+        .lock()
+        .unwrap()
+        .get(db_path)
+        .map(|key| to_hex(key))
+}
+
+/// Forgets the key remembered for `db_path`, called when its handle is closed.
+pub fn forget_key(db_path: &str) {
+    key_sessions().lock().unwrap().remove(db_path);
+}
@@ -0,0 +1,122 @@
+//! Durable, searchable log of `exec_sql`, `export_csv` and `export_json` invocations.
+//!
+//! Records are kept in an embedded sled store under the app data dir so the history
+//! survives across sessions without requiring a running database connection.
+
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+static HISTORY_DB: OnceLock<sled::Db> = OnceLock::new();
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    pub id: u64,
+    pub operation: String,
+    pub sql: String,
+    pub db_path: String,
+    pub timestamp_ms: u64,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub rows_affected: Option<u64>,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+fn history_db(app_handle: &AppHandle) -> Result<&'static sled::Db, String> {
+    if let Some(db) = HISTORY_DB.get() {
+        return Ok(db);
+    }
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Unable to resolve app data directory: {err}"))?;
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|err| format!("Unable to create app data directory: {err}"))?;
+    let db = sled::open(app_data_dir.join("history.sled"))
+        .map_err(|err| format!("Unable to open history store: {err}"))?;
+    Ok(HISTORY_DB.get_or_init(|| db))
+}
+
+/// Record one `exec_sql`/`export_csv`/`export_json` invocation. Failures to persist are swallowed
+/// so a history-store problem never breaks the query that triggered it.
+pub fn record(
+    app_handle: &AppHandle,
+    operation: &str,
+    sql: &str,
+    db_path: &str,
+    duration_ms: u64,
+    rows_affected: Option<u64>,
+    success: bool,
+    error: Option<String>,
+) {
+    let db = match history_db(app_handle) {
+        Ok(db) => db,
+        Err(_) => return,
+    };
+    let Ok(id) = db.generate_id() else {
+        return;
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let record = HistoryRecord {
+        id,
+        operation: operation.to_string(),
+        sql: sql.to_string(),
+        db_path: db_path.to_string(),
+        timestamp_ms,
+        duration_ms,
+        rows_affected,
+        success,
+        error,
+    };
+    if let Ok(encoded) = serde_json::to_vec(&record) {
+        let _ = db.insert(id.to_be_bytes(), encoded);
+    }
+}
+
+#[tauri::command]
+pub fn query_history(
+    app_handle: AppHandle,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<HistoryRecord>, String> {
+    let db = history_db(&app_handle)?;
+    let records: Vec<HistoryRecord> = db
+        .iter()
+        .rev()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|(_, value)| serde_json::from_slice(&value).ok())
+        .skip(offset)
+        .take(limit)
+        .collect();
+    Ok(records)
+}
+
+#[tauri::command]
+pub fn search_history(app_handle: AppHandle, substring: String) -> Result<Vec<HistoryRecord>, String> {
+    let db = history_db(&app_handle)?;
+    let needle = substring.to_ascii_lowercase();
+    let records: Vec<HistoryRecord> = db
+        .iter()
+        .rev()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|(_, value)| serde_json::from_slice::<HistoryRecord>(&value).ok())
+        .filter(|record| record.sql.to_ascii_lowercase().contains(&needle))
+        .collect();
+    Ok(records)
+}
+
+#[tauri::command]
+pub fn clear_history(app_handle: AppHandle) -> Result<(), String> {
+    let db = history_db(&app_handle)?;
+    db.clear().map_err(|err| format!("Unable to clear history: {err}"))?;
+    db.flush().map_err(|err| format!("Unable to flush history store: {err}"))?;
+    Ok(())
+}
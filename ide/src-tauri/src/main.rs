@@ -2,18 +2,29 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::OnceLock;
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use wait_timeout::ChildExt;
 
+mod backend;
+mod crypto;
+mod history;
+mod sqllogictest;
+mod watch;
+
+use crypto::DbOpenError;
+use sqllogictest::SqlLogicTestReport;
+
 const QUERY_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct QueryResultPayload {
+pub(crate) struct QueryResultPayload {
     columns: Vec<String>,
     rows: Vec<Vec<String>>,
     duration_ms: u64,
@@ -33,8 +44,8 @@ struct ExecResponse {
     result: Option<QueryResultPayload>,
 }
 
-struct CommandOutput {
-    stdout: String,
+pub(crate) struct CommandOutput {
+    pub(crate) stdout: String,
     stderr: String,
 }
 
@@ -116,45 +127,231 @@ fn granitectl_info() -> Result<GraniteCtlInfo, String> {
 }
 
 #[tauri::command]
-fn open_db(path: String) -> Result<(), String> {
-    let path = PathBuf::from(&path);
-    if !path.exists() {
-        return Err("Database file not found".into());
+fn open_db(path: String, passphrase: Option<String>) -> Result<(), DbOpenError> {
+    if backend::is_remote(&path) {
+        backend::resolve(&path).map_err(DbOpenError::other)?;
+        return Ok(());
     }
-    let metadata = fs::metadata(&path).map_err(|err| format!("Unable to read metadata: {err}"))?;
+
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        return Err(DbOpenError::NotFound {
+            message: "Database file not found".to_string(),
+        });
+    }
+    let metadata = fs::metadata(&path_buf)
+        .map_err(|err| DbOpenError::other(format!("Unable to read metadata: {err}")))?;
     if !metadata.is_file() {
-        return Err("Path must point to a file".into());
+        return Err(DbOpenError::other("Path must point to a file"));
     }
     fs::OpenOptions::new()
         .read(true)
-        .open(&path)
-        .map_err(|err| format!("Unable to open database: {err}"))?;
+        .open(&path_buf)
+        .map_err(|err| DbOpenError::other(format!("Unable to open database: {err}")))?;
+
+    match crypto::unlock(&path_buf, passphrase.as_deref())? {
+        Some(key) => crypto::remember_key(&path, key),
+        None => crypto::forget_key(&path),
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn create_db(path: String) -> Result<(), String> {
+fn create_db(path: String, passphrase: Option<String>) -> Result<(), DbOpenError> {
+    if backend::is_remote(&path) {
+        return Err(DbOpenError::other(
+            "create_db is not supported for remote connections",
+        ));
+    }
+
     let path = PathBuf::from(&path);
     if path.exists() {
-        return Err("Database file already exists".into());
+        return Err(DbOpenError::other("Database file already exists"));
     }
     if let Some(parent) = path.parent() {
         if !parent.as_os_str().is_empty() && !parent.exists() {
             fs::create_dir_all(parent)
-                .map_err(|err| format!("Unable to create parent directory: {err}"))?;
+                .map_err(|err| DbOpenError::other(format!("Unable to create parent directory: {err}")))?;
         }
     }
     let db = path
         .to_str()
-        .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
-    run_granitectl(&["new", db])?;
+        .ok_or_else(|| DbOpenError::other("Database path contains unsupported characters"))?;
+
+    match passphrase {
+        Some(passphrase) => {
+            let key = crypto::provision(&path, &passphrase)?;
+            run_granitectl(&["new", "--key-hex", &crypto::to_hex(&key), db])
+                .map_err(DbOpenError::other)?;
+            crypto::remember_key(db, key);
+        }
+        None => {
+            run_granitectl(&["new", db]).map_err(DbOpenError::other)?;
+        }
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn exec_sql(path: String, sql: String, format: String) -> Result<ExecResponse, String> {
-    if sql.trim().is_empty() {
-        return Err("SQL must not be empty".into());
+fn close_db(path: String) {
+    watch::close_db(&path);
+    crypto::forget_key(&path);
+}
+
+#[tauri::command]
+fn watch_table(path: String, table: String, last_seen_version: u64) -> u64 {
+    watch::watch_table(&path, &table, last_seen_version)
+}
+
+#[tauri::command]
+fn unwatch_table(path: String, table: String) {
+    watch::unwatch_table(&path, &table);
+}
+
+#[tauri::command]
+fn exec_sql(
+    app_handle: tauri::AppHandle,
+    path: String,
+    sql: String,
+    format: String,
+) -> Result<ExecResponse, String> {
+    // No local-file existence check here: `path` may be a `postgres://`/`http(s)://`
+    // connection string, and `backend::resolve` already enforces local existence
+    // (via `GranitectlBackend::open`) while letting remote connections through.
+    let db = path.as_str();
+
+    let start = Instant::now();
+    let outcome = exec_sql_impl(db, &sql, &format);
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let rows_affected = outcome
+        .as_ref()
+        .ok()
+        .and_then(|response| response.result.as_ref())
+        .and_then(|result| result.rows_affected);
+    history::record(
+        &app_handle,
+        "exec_sql",
+        &sql,
+        db,
+        duration_ms,
+        rows_affected,
+        outcome.is_ok(),
+        outcome.as_ref().err().cloned(),
+    );
+    if outcome.is_ok() {
+        if let Some(table) = watch::touched_table(&sql) {
+            watch::bump(&app_handle, db, &table);
+        }
+    }
+    outcome
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ScriptStep {
+    sql: String,
+    #[serde(default)]
+    delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptStepResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<ExecResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptExecReport {
+    steps: Vec<ScriptStepResult>,
+    rows_affected: u64,
+}
+
+#[tauri::command]
+fn exec_script(
+    path: String,
+    steps: Vec<ScriptStep>,
+    stop_on_error: bool,
+) -> Result<ScriptExecReport, String> {
+    // No local-file existence check here: see the matching comment in `exec_sql`.
+    let db = path.as_str();
+
+    let mut results = Vec::with_capacity(steps.len());
+    let mut rows_affected = 0u64;
+
+    for step in steps {
+        if let Some(delay_ms) = step.delay_ms {
+            std::thread::sleep(Duration::from_millis(delay_ms));
+        }
+        match exec_sql_impl(db, &step.sql, "jsonRows") {
+            Ok(response) => {
+                if let Some(result) = &response.result {
+                    rows_affected += result.rows_affected.unwrap_or(0);
+                }
+                results.push(ScriptStepResult {
+                    response: Some(response),
+                    error: None,
+                });
+            }
+            Err(err) => {
+                let halt = stop_on_error;
+                results.push(ScriptStepResult {
+                    response: None,
+                    error: Some(err),
+                });
+                if halt {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(ScriptExecReport {
+        steps: results,
+        rows_affected,
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchStatementResult {
+    sql: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<QueryResultPayload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchExecReport {
+    statements: Vec<BatchStatementResult>,
+    status: String,
+    /// `false` means granitectl's combined output couldn't be reliably split
+    /// one-to-one with the input statements (e.g. `BEGIN`/`COMMIT` added their
+    /// own blocks, or a statement's own output happened to contain a blank
+    /// line), so `statements` holds a single entry covering the whole script
+    /// rather than one result per statement. The transaction still committed
+    /// or rolled back as a whole either way — this only affects how finely
+    /// the result can be reported.
+    per_statement_results: bool,
+}
+
+/// Runs every statement as one `;`-delimited script inside a single granitectl
+/// invocation, wrapped in an explicit `BEGIN`/`COMMIT`, so the transaction actually
+/// spans all of them. A separate process per statement can't offer this guarantee:
+/// each `BEGIN`/`COMMIT`/`ROLLBACK` would only affect its own short-lived connection.
+#[tauri::command]
+fn exec_batch(
+    app_handle: tauri::AppHandle,
+    path: String,
+    script: String,
+) -> Result<BatchExecReport, String> {
+    if backend::is_remote(&path) {
+        return Err("exec_batch is not supported for remote connections".into());
     }
     let db_path = Path::new(&path);
     if !db_path.exists() {
@@ -163,140 +360,646 @@ fn exec_sql(path: String, sql: String, format: String) -> Result<ExecResponse, S
     let db = db_path
         .to_str()
         .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
-    match format.as_str() {
-        "jsonRows" => match run_granitectl(&["exec", "--format", "json", "-q", &sql, db]) {
-            Ok(output) => {
-                let payload: QueryResultPayload = serde_json::from_str(&output.stdout)
-                    .map_err(|err| format!("Failed to parse JSON output: {err}"))?;
-                Ok(ExecResponse {
-                    format,
-                    output: None,
-                    result: Some(payload),
-                })
+    let statements = split_sql_statements(&script);
+    if statements.is_empty() {
+        return Err("SQL must not be empty".into());
+    }
+
+    let wrapped = format!("BEGIN;\n{};\nCOMMIT;", statements.join(";\n"));
+    let start = Instant::now();
+    let key_hex = crypto::key_hex_for(db);
+    let args = with_key_args(&["exec", "--format", "table", "-q", &wrapped, db], key_hex.as_deref());
+
+    match run_granitectl(&args) {
+        Ok(output) => {
+            let (results, per_statement_results) =
+                match per_statement_blocks(&output.stdout, statements.len()) {
+                    Some(blocks) => (
+                        statements
+                            .iter()
+                            .zip(blocks.iter())
+                            .map(|(statement, block)| {
+                                match parse_legacy_exec_output(block, start.elapsed()) {
+                                    Ok(payload) => BatchStatementResult {
+                                        sql: statement.clone(),
+                                        result: Some(payload),
+                                        error: None,
+                                    },
+                                    Err(err) => BatchStatementResult {
+                                        sql: statement.clone(),
+                                        result: None,
+                                        error: Some(err),
+                                    },
+                                }
+                            })
+                            .collect(),
+                        true,
+                    ),
+                    None => (
+                        // granitectl's output couldn't be split one-to-one with the
+                        // input statements; report the whole script as a single
+                        // result rather than fabricating per-statement granularity.
+                        vec![BatchStatementResult {
+                            sql: statements.join(";\n"),
+                            result: parse_legacy_exec_output(&output.stdout, start.elapsed()).ok(),
+                            error: None,
+                        }],
+                        false,
+                    ),
+                };
+
+            let mut touched_tables: Vec<String> =
+                statements.iter().filter_map(|sql| watch::touched_table(sql)).collect();
+            touched_tables.sort();
+            touched_tables.dedup();
+            for table in &touched_tables {
+                watch::bump(&app_handle, db, table);
             }
-            Err(err) => {
-                if is_unknown_format_error(&err) {
-                    let payload = legacy_exec_result(db, &sql)?;
-                    Ok(ExecResponse {
-                        format,
-                        output: None,
-                        result: Some(payload),
-                    })
-                } else {
-                    Err(err)
+
+            Ok(BatchExecReport {
+                statements: results,
+                status: "committed".to_string(),
+                per_statement_results,
+            })
+        }
+        Err(err) => {
+            // The whole script ran as one transaction in one process; any failure
+            // rolls every statement in it back, so nothing here was persisted.
+            Ok(BatchExecReport {
+                statements: vec![BatchStatementResult {
+                    sql: statements.join(";\n"),
+                    result: None,
+                    error: Some(err),
+                }],
+                per_statement_results: false,
+                status: "rolledBack".to_string(),
+            })
+        }
+    }
+}
+
+/// Splits granitectl's combined table output for a wrapped `BEGIN; <statements>;
+/// COMMIT;` script back into one block per *input* statement, assuming each
+/// statement's result is separated from the next by a blank line and contains
+/// no blank line of its own — or `None` if the output can't be matched up
+/// one-to-one with `statement_count`, in which case the caller falls back to
+/// reporting the whole script as a single combined result.
+///
+/// Splitting is ambiguous because `BEGIN`/`COMMIT` ride along in the same
+/// invocation as the statements and may or may not add blocks of their own:
+/// a block count equal to `statement_count` means they produced no output, a
+/// count of `statement_count + 2` means they each produced their own (now
+/// discarded) block, and anything else means a statement's own output itself
+/// contained a blank line, so the blocks can't be trusted to line up at all.
+fn per_statement_blocks(output: &str, statement_count: usize) -> Option<Vec<String>> {
+    let blocks: Vec<String> = output
+        .split("\n\n")
+        .map(|block| block.trim())
+        .filter(|block| !block.is_empty())
+        .map(|block| block.to_string())
+        .collect();
+
+    if blocks.len() == statement_count {
+        Some(blocks)
+    } else if blocks.len() == statement_count + 2 {
+        Some(blocks[1..blocks.len() - 1].to_vec())
+    } else {
+        None
+    }
+}
+
+/// Splits a `;`-delimited SQL script into individual statements, ignoring
+/// semicolons that appear inside single- or double-quoted string literals.
+fn split_sql_statements(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+
+    for ch in script.chars() {
+        match ch {
+            '\'' if !in_double_quote => {
+                in_single_quote = !in_single_quote;
+                current.push(ch);
+            }
+            '"' if !in_single_quote => {
+                in_double_quote = !in_double_quote;
+                current.push(ch);
+            }
+            ';' if !in_single_quote && !in_double_quote => {
+                let trimmed = current.trim();
+                if !trimmed.is_empty() {
+                    statements.push(trimmed.to_string());
                 }
+                current.clear();
             }
-        },
-        "table" | "csv" => {
-            let output = run_granitectl(&["exec", "--format", &format, "-q", &sql, db])?;
-            Ok(ExecResponse {
-                format,
-                output: Some(output.stdout),
-                result: None,
-            })
+            _ => current.push(ch),
         }
-        other => Err(format!("Unsupported format {other}")),
+    }
+    let trimmed = current.trim();
+    if !trimmed.is_empty() {
+        statements.push(trimmed.to_string());
+    }
+    statements
+}
+
+fn exec_sql_impl(db: &str, sql: &str, format: &str) -> Result<ExecResponse, String> {
+    match backend::resolve(db)?.exec(sql, format)? {
+        backend::ExecOutput::Json(payload) => Ok(ExecResponse {
+            format: format.to_string(),
+            output: None,
+            result: Some(payload),
+        }),
+        backend::ExecOutput::Raw(output) => Ok(ExecResponse {
+            format: format.to_string(),
+            output: Some(output),
+            result: None,
+        }),
     }
 }
 
 #[tauri::command]
 fn explain_sql(path: String, sql: String) -> Result<String, String> {
+    backend::resolve(&path)?.explain(&sql)
+}
+
+#[tauri::command]
+fn metadata(path: String) -> Result<String, String> {
+    backend::resolve(&path)?.metadata()
+}
+
+#[tauri::command]
+fn export_csv(
+    app_handle: tauri::AppHandle,
+    path: String,
+    sql: String,
+    out_path: String,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let db = Path::new(&path)
+        .to_str()
+        .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
+    let outcome = export_csv_impl(db, &sql, &out_path);
+    let duration_ms = start.elapsed().as_millis() as u64;
+    history::record(
+        &app_handle,
+        "export_csv",
+        &sql,
+        db,
+        duration_ms,
+        None,
+        outcome.is_ok(),
+        outcome.as_ref().err().cloned(),
+    );
+    outcome
+}
+
+fn export_csv_impl(db: &str, sql: &str, out_path: &str) -> Result<(), String> {
+    let csv = backend::resolve(db)?.export(sql, "csv")?;
+    fs::write(out_path, csv).map_err(|err| format!("Failed to write CSV: {err}"))?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum ExportJsonFormat {
+    Array,
+    Ndjson,
+}
+
+#[tauri::command]
+fn export_json(
+    app_handle: tauri::AppHandle,
+    path: String,
+    sql: String,
+    out_path: String,
+    format: ExportJsonFormat,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let db = Path::new(&path)
+        .to_str()
+        .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
+    let outcome = export_json_impl(db, &sql, &out_path, format);
+    let duration_ms = start.elapsed().as_millis() as u64;
+    history::record(
+        &app_handle,
+        "export_json",
+        &sql,
+        db,
+        duration_ms,
+        None,
+        outcome.is_ok(),
+        outcome.as_ref().err().cloned(),
+    );
+    outcome
+}
+
+/// For a local database, streams rows straight from granitectl's piped stdout
+/// (via [`export_json_streaming`]) so a large export's memory use is bounded by
+/// one row at a time rather than the whole result set. Remote connections have
+/// no local process to stream from, so they fall back to the buffered
+/// `backend::resolve(db)?.exec` round trip (see [`export_json_buffered`]).
+fn export_json_impl(
+    db: &str,
+    sql: &str,
+    out_path: &str,
+    format: ExportJsonFormat,
+) -> Result<(), String> {
+    if backend::is_remote(db) {
+        export_json_buffered(db, sql, out_path, format)
+    } else {
+        export_json_streaming(db, sql, out_path, format)
+    }
+}
+
+fn export_json_buffered(
+    db: &str,
+    sql: &str,
+    out_path: &str,
+    format: ExportJsonFormat,
+) -> Result<(), String> {
+    let payload = match backend::resolve(db)?.exec(sql, "jsonRows")? {
+        backend::ExecOutput::Json(payload) => payload,
+        backend::ExecOutput::Raw(_) => {
+            return Err("Expected a structured result for JSON export".into())
+        }
+    };
+
+    let file =
+        fs::File::create(out_path).map_err(|err| format!("Failed to create export file: {err}"))?;
+    let mut rows_writer = JsonRowsWriter::new(BufWriter::new(file), format)?;
+    for row in &payload.rows {
+        rows_writer.write_row(&payload.columns, row)?;
+    }
+    rows_writer.finish()
+}
+
+/// Bypasses `run_granitectl`/`Backend::exec`, which both buffer the whole
+/// invocation's stdout before returning, and `--format json`, which is a
+/// single JSON document rather than one record at a time. Instead this spawns
+/// granitectl directly with `--format csv` and parses its stdout pipe
+/// incrementally with `CsvRowReader`, writing each row out as soon as it's
+/// parsed so a multi-million-row export never holds the whole result set in
+/// memory.
+fn export_json_streaming(
+    db: &str,
+    sql: &str,
+    out_path: &str,
+    format: ExportJsonFormat,
+) -> Result<(), String> {
     if sql.trim().is_empty() {
         return Err("SQL must not be empty".into());
     }
-    let db_path = Path::new(&path);
-    if !db_path.exists() {
+    if !Path::new(db).exists() {
         return Err("Database file not found".into());
     }
-    let db = db_path
-        .to_str()
-        .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
-    let output = run_granitectl(&["explain", "--json", "-q", &sql, db])?;
-    Ok(output.stdout)
+
+    let key_hex = crypto::key_hex_for(db);
+    let args = with_key_args(&["exec", "--format", "csv", "-q", sql, db], key_hex.as_deref());
+
+    let (path, source) = granitectl_resolution();
+    log_granitectl_resolution(&path, source);
+    if !matches!(source, GraniteCtlSource::System) && !path.exists() {
+        return Err(missing_granitectl_message(&path, source));
+    }
+
+    let mut command = Command::new(&path);
+    command.args(&args);
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    log_granitectl_debug(&path, &args);
+    let mut child = command.spawn().map_err(|err| match err.kind() {
+        ErrorKind::NotFound => missing_granitectl_message(&path, source),
+        _ => format!("Failed to run granitectl: {err}"),
+    })?;
+
+    let mut child_stderr = child.stderr.take();
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to capture granitectl output".to_string())?;
+
+    // A hung granitectl would otherwise block the row-reading loop below
+    // forever, since (unlike `run_granitectl`) nothing here waits on the
+    // child up front. Kill it after QUERY_TIMEOUT unless reading finishes
+    // (and so disarms the watchdog) first.
+    let reading_done = Arc::new(AtomicBool::new(false));
+    let child = Arc::new(Mutex::new(child));
+    {
+        let reading_done = Arc::clone(&reading_done);
+        let child = Arc::clone(&child);
+        thread::spawn(move || {
+            thread::sleep(QUERY_TIMEOUT);
+            if !reading_done.load(Ordering::SeqCst) {
+                let _ = child.lock().unwrap().kill();
+            }
+        });
+    }
+
+    let rows_result = (|| -> Result<(), String> {
+        let mut rows = CsvRowReader::new(stdout);
+        let columns = rows.next_row()?.unwrap_or_default();
+        let file = fs::File::create(out_path)
+            .map_err(|err| format!("Failed to create export file: {err}"))?;
+        let mut rows_writer = JsonRowsWriter::new(BufWriter::new(file), format)?;
+        while let Some(row) = rows.next_row()? {
+            rows_writer.write_row(&columns, &row)?;
+        }
+        rows_writer.finish()
+    })();
+    reading_done.store(true, Ordering::SeqCst);
+
+    let status = child
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|err| format!("Failed to await granitectl: {err}"))?;
+    if !status.success() {
+        let mut message = String::new();
+        if let Some(stderr) = child_stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut message);
+        }
+        let message = message.trim();
+        return Err(if message.is_empty() {
+            "granitectl returned an error".to_string()
+        } else {
+            message.to_string()
+        });
+    }
+
+    rows_result
 }
 
-#[tauri::command]
-fn metadata(path: String) -> Result<String, String> {
-    let db_path = Path::new(&path);
-    if !db_path.exists() {
-        return Err("Database file not found".into());
+/// Reads CSV records one byte at a time from granitectl's `--format csv`
+/// stdout, so a caller can act on each row as it arrives instead of waiting
+/// for the whole export to finish. Handles the same quoting `--format csv`
+/// itself produces: fields wrapped in `"..."` with `""`-escaped quotes,
+/// allowing embedded commas and newlines.
+struct CsvRowReader<R: Read> {
+    reader: BufReader<R>,
+}
+
+impl<R: Read> CsvRowReader<R> {
+    fn new(reader: R) -> Self {
+        CsvRowReader {
+            reader: BufReader::new(reader),
+        }
     }
-    let db = db_path
-        .to_str()
-        .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
-    match run_granitectl(&["meta", "--json", db]) {
-        Ok(output) => {
-            if !looks_like_json(&output.stdout) {
-                let preview = output.stdout.trim();
-                if preview.contains("unknown command") {
-                    let legacy = legacy_metadata(db)?;
-                    return Ok(legacy);
+
+    fn read_byte(&mut self) -> Result<Option<u8>, String> {
+        let mut byte = [0u8; 1];
+        match self
+            .reader
+            .read(&mut byte)
+            .map_err(|err| format!("Failed to read export stream: {err}"))?
+        {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Returns the next record, or `None` once the stream is exhausted.
+    fn next_row(&mut self) -> Result<Option<Vec<String>>, String> {
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut in_quotes = false;
+        let mut saw_any_byte = false;
+
+        loop {
+            let Some(byte) = self.read_byte()? else {
+                if !saw_any_byte {
+                    return Ok(None);
                 }
-                let message = if preview.is_empty() {
-                    "granitectl returned no metadata".to_string()
+                fields.push(take_utf8_field(&mut field)?);
+                return Ok(Some(fields));
+            };
+            saw_any_byte = true;
+
+            if in_quotes {
+                if byte == b'"' {
+                    if self.peek_is_quote()? {
+                        self.read_byte()?;
+                        field.push(b'"');
+                    } else {
+                        in_quotes = false;
+                    }
                 } else {
-                    format!("granitectl metadata output was not JSON: {preview}")
-                };
-                return Err(message);
+                    field.push(byte);
+                }
+                continue;
             }
-            Ok(output.stdout)
-        }
-        Err(err) => {
-            if err.contains("unknown command") {
-                let legacy = legacy_metadata(db)?;
-                Ok(legacy)
-            } else {
-                Err(err)
+
+            match byte {
+                b'"' => in_quotes = true,
+                b',' => fields.push(take_utf8_field(&mut field)?),
+                b'\r' => {}
+                b'\n' => {
+                    fields.push(take_utf8_field(&mut field)?);
+                    return Ok(Some(fields));
+                }
+                other => field.push(other),
             }
         }
     }
+
+    fn peek_is_quote(&mut self) -> Result<bool, String> {
+        let buf = self
+            .reader
+            .fill_buf()
+            .map_err(|err| format!("Failed to read export stream: {err}"))?;
+        Ok(buf.first() == Some(&b'"'))
+    }
+}
+
+fn take_utf8_field(field: &mut Vec<u8>) -> Result<String, String> {
+    String::from_utf8(std::mem::take(field))
+        .map_err(|err| format!("Export stream was not valid UTF-8: {err}"))
+}
+
+/// Incrementally writes export rows as either a JSON array or NDJSON, so
+/// neither the streaming nor the buffered export path has to assemble the
+/// whole document in memory before writing it out.
+struct JsonRowsWriter<W: Write> {
+    writer: W,
+    format: ExportJsonFormat,
+    wrote_any: bool,
+}
+
+impl<W: Write> JsonRowsWriter<W> {
+    fn new(mut writer: W, format: ExportJsonFormat) -> Result<Self, String> {
+        if matches!(format, ExportJsonFormat::Array) {
+            writer
+                .write_all(b"[")
+                .map_err(|err| format!("Failed to write export file: {err}"))?;
+        }
+        Ok(JsonRowsWriter {
+            writer,
+            format,
+            wrote_any: false,
+        })
+    }
+
+    fn write_row(&mut self, columns: &[String], row: &[String]) -> Result<(), String> {
+        if matches!(self.format, ExportJsonFormat::Array) && self.wrote_any {
+            self.writer
+                .write_all(b",")
+                .map_err(|err| format!("Failed to write export file: {err}"))?;
+        }
+        write_row_object(&mut self.writer, columns, row)?;
+        if matches!(self.format, ExportJsonFormat::Ndjson) {
+            self.writer
+                .write_all(b"\n")
+                .map_err(|err| format!("Failed to write export file: {err}"))?;
+        }
+        self.wrote_any = true;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        if matches!(self.format, ExportJsonFormat::Array) {
+            self.writer
+                .write_all(b"]")
+                .map_err(|err| format!("Failed to write export file: {err}"))?;
+        }
+        self.writer
+            .flush()
+            .map_err(|err| format!("Failed to flush export file: {err}"))?;
+        Ok(())
+    }
+}
+
+fn write_row_object(
+    writer: &mut impl Write,
+    columns: &[String],
+    row: &[String],
+) -> Result<(), String> {
+    let object: serde_json::Map<String, serde_json::Value> = columns
+        .iter()
+        .cloned()
+        .zip(row.iter().cloned().map(serde_json::Value::String))
+        .collect();
+    serde_json::to_writer(writer, &serde_json::Value::Object(object))
+        .map_err(|err| format!("Failed to write export row: {err}"))
 }
 
 #[tauri::command]
-fn export_csv(path: String, sql: String, out_path: String) -> Result<(), String> {
-    if sql.trim().is_empty() {
-        return Err("SQL must not be empty".into());
+fn run_sqllogictest(path: String, script: String) -> Result<SqlLogicTestReport, String> {
+    if backend::is_remote(&path) {
+        return Err("run_sqllogictest is not supported for remote connections".into());
     }
-    let db = Path::new(&path)
+    let db_path = Path::new(&path);
+    if !db_path.exists() {
+        return Err("Database file not found".into());
+    }
+    let db = db_path
         .to_str()
         .ok_or_else(|| "Database path contains unsupported characters".to_string())?;
-    let output = run_granitectl(&["exec", "--format", "csv", "-q", &sql, db])?;
-    fs::write(&out_path, output.stdout).map_err(|err| format!("Failed to write CSV: {err}"))?;
-    Ok(())
+    let script_path = Path::new(&script);
+    let contents = fs::read_to_string(script_path)
+        .map_err(|err| format!("Failed to read sqllogictest script: {err}"))?;
+    sqllogictest::run_sqllogictest(db, &contents)
+}
+
+/// Outcome of a single granitectl invocation attempt, distinguishing failures worth
+/// retrying (a busy spawn, a stalled process) from ones that will never succeed.
+///
+/// `TimedOut` is kept separate from `Transient`: it means we killed the child after
+/// it had already been running for up to `QUERY_TIMEOUT`, so for a mutating command
+/// (`exec`/`exec_batch`/`new`) it may have partially applied its statement(s) before
+/// being killed. Retrying it would risk re-running (and so double-applying) that
+/// command, so it's only retried when the caller has told us the invocation is
+/// read-only/idempotent via [`run_granitectl_readonly`].
+enum GraniteCtlAttemptError {
+    Permanent(String),
+    Transient(String),
+    TimedOut(String),
+}
+
+/// Runs `args` against granitectl. A command that times out is killed and the
+/// failure is returned as-is, without a retry — see [`GraniteCtlAttemptError::TimedOut`]
+/// for why that's not safe in general. Use [`run_granitectl_readonly`] instead for
+/// commands (`explain`, `meta`, a sqllogictest `query` record) that are safe to
+/// retry even after being killed mid-flight.
+pub(crate) fn run_granitectl(args: &[&str]) -> Result<CommandOutput, String> {
+    run_granitectl_inner(args, false)
+}
+
+/// Like [`run_granitectl`], but also retries an invocation that was killed for
+/// timing out. Only call this for read-only/idempotent commands: a mutating
+/// command that's killed after timing out may have already partially applied its
+/// side effects, and retrying it would risk running it twice.
+pub(crate) fn run_granitectl_readonly(args: &[&str]) -> Result<CommandOutput, String> {
+    run_granitectl_inner(args, true)
 }
 
-fn run_granitectl(args: &[&str]) -> Result<CommandOutput, String> {
+fn run_granitectl_inner(args: &[&str], retry_on_timeout: bool) -> Result<CommandOutput, String> {
     let (path, source) = granitectl_resolution();
     log_granitectl_resolution(&path, source);
     if !matches!(source, GraniteCtlSource::System) && !path.exists() {
         return Err(missing_granitectl_message(&path, source));
     }
 
-    let mut command = Command::new(&path);
+    let max_retries = granitectl_max_retries();
+    let base_delay = granitectl_retry_base_delay();
+    let cap_delay = granitectl_retry_cap_delay();
+
+    let mut attempt = 0;
+    loop {
+        match run_granitectl_attempt(&path, source, args) {
+            Ok(output) => return Ok(output),
+            Err(GraniteCtlAttemptError::Permanent(message)) => return Err(message),
+            Err(GraniteCtlAttemptError::TimedOut(message)) => {
+                if !retry_on_timeout || attempt >= max_retries {
+                    return Err(message);
+                }
+                std::thread::sleep(granitectl_backoff_delay(attempt, base_delay, cap_delay));
+                attempt += 1;
+            }
+            Err(GraniteCtlAttemptError::Transient(message)) => {
+                if attempt >= max_retries {
+                    return Err(message);
+                }
+                std::thread::sleep(granitectl_backoff_delay(attempt, base_delay, cap_delay));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+fn run_granitectl_attempt(
+    path: &Path,
+    source: GraniteCtlSource,
+    args: &[&str],
+) -> Result<CommandOutput, GraniteCtlAttemptError> {
+    let mut command = Command::new(path);
     command.args(args);
     command.stdout(Stdio::piped());
     command.stderr(Stdio::piped());
-    log_granitectl_debug(&path, args);
+    log_granitectl_debug(path, args);
     let mut child = command.spawn().map_err(|err| match err.kind() {
-        ErrorKind::NotFound => missing_granitectl_message(&path, source),
-        _ => format!("Failed to run granitectl: {err}"),
+        ErrorKind::NotFound => {
+            GraniteCtlAttemptError::Permanent(missing_granitectl_message(path, source))
+        }
+        _ => GraniteCtlAttemptError::Transient(format!("Failed to run granitectl: {err}")),
     })?;
 
     match child.wait_timeout(QUERY_TIMEOUT) {
         Ok(Some(_)) => {}
         Ok(None) => {
             let _ = child.kill();
-            return Err("granitectl timed out".into());
+            return Err(GraniteCtlAttemptError::TimedOut("granitectl timed out".into()));
         }
         Err(err) => {
             let _ = child.kill();
-            return Err(format!("Failed to await granitectl: {err}"));
+            return Err(GraniteCtlAttemptError::TimedOut(format!(
+                "Failed to await granitectl: {err}"
+            )));
         }
     }
 
-    let output = child
-        .wait_with_output()
-        .map_err(|err| format!("Failed to read granitectl output: {err}"))?;
+    let output = child.wait_with_output().map_err(|err| {
+        GraniteCtlAttemptError::Transient(format!("Failed to read granitectl output: {err}"))
+    })?;
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
     if !output.status.success() {
@@ -305,13 +1008,54 @@ fn run_granitectl(args: &[&str]) -> Result<CommandOutput, String> {
         } else {
             stderr.trim().to_string()
         };
-        return Err(err_msg);
+        return Err(GraniteCtlAttemptError::Permanent(err_msg));
     }
 
     Ok(CommandOutput { stdout, stderr })
 }
 
-fn looks_like_json(output: &str) -> bool {
+fn granitectl_max_retries() -> u32 {
+    env_var_parsed("GRANITECTL_MAX_RETRIES").unwrap_or(3)
+}
+
+fn granitectl_retry_base_delay() -> Duration {
+    env_var_parsed("GRANITECTL_RETRY_BASE_MS")
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_millis(50))
+}
+
+fn granitectl_retry_cap_delay() -> Duration {
+    env_var_parsed("GRANITECTL_RETRY_CAP_MS")
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_millis(2000))
+}
+
+fn env_var_parsed<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+/// Exponential backoff (doubling each attempt, capped) with a little jitter so
+/// concurrent retries don't all wake up at once.
+fn granitectl_backoff_delay(attempt: u32, base: Duration, cap: Duration) -> Duration {
+    let scaled = base
+        .checked_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+        .unwrap_or(cap);
+    let capped = scaled.min(cap);
+    capped + Duration::from_millis(jitter_ms(capped.as_millis() as u64 / 10))
+}
+
+fn jitter_ms(max: u64) -> u64 {
+    if max == 0 {
+        return 0;
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos() as u64)
+        .unwrap_or(0);
+    nanos % (max + 1)
+}
+
+pub(crate) fn looks_like_json(output: &str) -> bool {
     let trimmed = output.trim_start();
     matches!(trimmed.chars().next(), Some('{') | Some('['))
 }
@@ -381,17 +1125,37 @@ fn log_granitectl_resolution(path: &Path, source: GraniteCtlSource) {
     });
 }
 
-fn is_unknown_format_error(message: &str) -> bool {
+pub(crate) fn is_unknown_format_error(message: &str) -> bool {
     let lower = message.to_ascii_lowercase();
     lower.contains("unknown format") || lower.contains("json format is only supported")
 }
 
-fn legacy_exec_result(db: &str, sql: &str) -> Result<QueryResultPayload, String> {
+pub(crate) fn legacy_exec_result(db: &str, sql: &str) -> Result<QueryResultPayload, String> {
     let start = Instant::now();
-    let output = run_granitectl(&["exec", "--format", "table", "-q", sql, db])?;
+    let key_hex = crypto::key_hex_for(db);
+    let args = with_key_args(&["exec", "--format", "table", "-q", sql, db], key_hex.as_deref());
+    let output = run_granitectl(&args)?;
     parse_legacy_exec_output(&output.stdout, start.elapsed())
 }
 
+/// Inserts `--key-hex` ahead of the trailing db-path argument when `key_hex`
+/// is set, i.e. the db was unlocked with a passphrase earlier in this session
+/// (see [`crypto::key_hex_for`]). Every granitectl call is a fresh process, so
+/// the key has to be re-supplied on each invocation rather than living on a
+/// persistent connection.
+pub(crate) fn with_key_args<'a>(args: &[&'a str], key_hex: Option<&'a str>) -> Vec<&'a str> {
+    let mut args = args.to_vec();
+    if let Some(key_hex) = key_hex {
+        let db_arg = args.pop();
+        args.push("--key-hex");
+        args.push(key_hex);
+        if let Some(db_arg) = db_arg {
+            args.push(db_arg);
+        }
+    }
+    args
+}
+
 fn parse_legacy_exec_output(
     output: &str,
     duration: Duration,
@@ -501,8 +1265,10 @@ fn extract_rows_affected(message: &str) -> Option<u64> {
     digits.parse().ok().filter(|value: &u64| *value > 0)
 }
 
-fn legacy_metadata(db: &str) -> Result<String, String> {
-    let output = run_granitectl(&["dump", db])?;
+pub(crate) fn legacy_metadata(db: &str) -> Result<String, String> {
+    let key_hex = crypto::key_hex_for(db);
+    let args = with_key_args(&["dump", db], key_hex.as_deref());
+    let output = run_granitectl_readonly(&args)?;
     parse_legacy_metadata(&output.stdout)
 }
 
@@ -916,10 +1682,20 @@ fn main() {
             granitectl_info,
             open_db,
             create_db,
+            close_db,
+            watch_table,
+            unwatch_table,
             exec_sql,
+            exec_script,
+            exec_batch,
             explain_sql,
             metadata,
-            export_csv
+            export_csv,
+            export_json,
+            run_sqllogictest,
+            history::query_history,
+            history::search_history,
+            history::clear_history
         ])
         .run(tauri::generate_context!())
         .expect("error while running Granite IDE application");
@@ -0,0 +1,453 @@
+//! sqllogictest-style regression runner for exercising a database through granitectl.
+//!
+//! Parses a subset of the sqllogictest record format (statement/query/halt/hash-threshold)
+//! and replays each record against `run_granitectl`, comparing observed results to the
+//! expectations encoded in the script.
+
+use serde::Serialize;
+
+use crate::{crypto, run_granitectl, run_granitectl_readonly, with_key_args, QueryResultPayload};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SqlLogicTestReport {
+    pub records: Vec<SqlLogicTestRecordResult>,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SqlLogicTestRecordResult {
+    pub line: usize,
+    pub kind: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    NoSort,
+    RowSort,
+    ValueSort,
+}
+
+impl SortMode {
+    fn parse(token: &str) -> Result<Self, String> {
+        match token {
+            "nosort" => Ok(SortMode::NoSort),
+            "rowsort" => Ok(SortMode::RowSort),
+            "valuesort" => Ok(SortMode::ValueSort),
+            other => Err(format!("unknown sort mode: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Text,
+    Integer,
+    Real,
+}
+
+impl ColumnType {
+    fn parse(ch: char) -> Result<Self, String> {
+        match ch {
+            'T' => Ok(ColumnType::Text),
+            'I' => Ok(ColumnType::Integer),
+            'R' => Ok(ColumnType::Real),
+            other => Err(format!("unknown column type: {other}")),
+        }
+    }
+}
+
+enum Record {
+    Statement {
+        line: usize,
+        expect_error: bool,
+        sql: String,
+    },
+    Query {
+        line: usize,
+        types: Vec<ColumnType>,
+        sort_mode: SortMode,
+        sql: String,
+        expected: Vec<String>,
+    },
+    HashThreshold {
+        value: usize,
+    },
+    Halt {
+        line: usize,
+    },
+}
+
+pub fn run_sqllogictest(db: &str, script: &str) -> Result<SqlLogicTestReport, String> {
+    let key_hex = crypto::key_hex_for(db);
+    let records = parse_script(script)?;
+    let mut hash_threshold: Option<usize> = None;
+    let mut results = Vec::new();
+
+    for record in records {
+        match record {
+            Record::HashThreshold { value } => hash_threshold = Some(value),
+            Record::Halt { line } => {
+                results.push(SqlLogicTestRecordResult {
+                    line,
+                    kind: "halt".into(),
+                    status: "pass".into(),
+                    expected: None,
+                    actual: None,
+                    error: None,
+                });
+                break;
+            }
+            Record::Statement {
+                line,
+                expect_error,
+                sql,
+            } => results.push(run_statement(db, key_hex.as_deref(), line, expect_error, &sql)),
+            Record::Query {
+                line,
+                types,
+                sort_mode,
+                sql,
+                expected,
+            } => results.push(run_query(
+                db,
+                key_hex.as_deref(),
+                line,
+                &types,
+                sort_mode,
+                &sql,
+                expected,
+                hash_threshold,
+            )),
+        }
+    }
+
+    let passed = results.iter().filter(|r| r.status == "pass").count();
+    let failed = results.len() - passed;
+    Ok(SqlLogicTestReport {
+        records: results,
+        passed,
+        failed,
+    })
+}
+
+fn run_statement(
+    db: &str,
+    key_hex: Option<&str>,
+    line: usize,
+    expect_error: bool,
+    sql: &str,
+) -> SqlLogicTestRecordResult {
+    let args = with_key_args(&["exec", "--format", "table", "-q", sql, db], key_hex);
+    let outcome = run_granitectl(&args);
+    let (status, error) = match (expect_error, outcome) {
+        (false, Ok(_)) => ("pass".to_string(), None),
+        (true, Err(_)) => ("pass".to_string(), None),
+        (false, Err(err)) => ("fail".to_string(), Some(err)),
+        (true, Ok(_)) => (
+            "fail".to_string(),
+            Some("statement succeeded but an error was expected".to_string()),
+        ),
+    };
+    SqlLogicTestRecordResult {
+        line,
+        kind: "statement".into(),
+        status,
+        expected: None,
+        actual: None,
+        error,
+    }
+}
+
+fn run_query(
+    db: &str,
+    key_hex: Option<&str>,
+    line: usize,
+    types: &[ColumnType],
+    sort_mode: SortMode,
+    sql: &str,
+    expected: Vec<String>,
+    hash_threshold: Option<usize>,
+) -> SqlLogicTestRecordResult {
+    // A `query` record is read-only by sqllogictest convention, so it's safe to
+    // retry even if a prior attempt was killed for timing out.
+    let args = with_key_args(&["exec", "--format", "json", "-q", sql, db], key_hex);
+    let payload = match run_granitectl_readonly(&args)
+        .and_then(|output| {
+            serde_json::from_str::<QueryResultPayload>(&output.stdout)
+                .map_err(|err| format!("Failed to parse JSON output: {err}"))
+        }) {
+        Ok(payload) => payload,
+        Err(err) => {
+            return SqlLogicTestRecordResult {
+                line,
+                kind: "query".into(),
+                status: "fail".into(),
+                expected: Some(expected),
+                actual: None,
+                error: Some(err),
+            }
+        }
+    };
+
+    let actual_values = match coerce_values(&payload, types) {
+        Ok(values) => values,
+        Err(err) => {
+            return SqlLogicTestRecordResult {
+                line,
+                kind: "query".into(),
+                status: "fail".into(),
+                expected: Some(expected),
+                actual: None,
+                error: Some(err),
+            }
+        }
+    };
+
+    let expected_coerced = match coerce_expected(&expected, types) {
+        Ok(values) => values,
+        Err(err) => {
+            return SqlLogicTestRecordResult {
+                line,
+                kind: "query".into(),
+                status: "fail".into(),
+                expected: Some(expected),
+                actual: None,
+                error: Some(err),
+            }
+        }
+    };
+
+    let actual_sorted = apply_sort_mode(actual_values, types.len(), sort_mode);
+    let expected_sorted = apply_sort_mode(expected_coerced, types.len(), sort_mode);
+
+    let matches = match hash_threshold {
+        Some(threshold) if actual_sorted.len() > threshold => {
+            digest(&actual_sorted) == digest(&expected_sorted)
+        }
+        _ => actual_sorted == expected_sorted,
+    };
+
+    if matches {
+        SqlLogicTestRecordResult {
+            line,
+            kind: "query".into(),
+            status: "pass".into(),
+            expected: None,
+            actual: None,
+            error: None,
+        }
+    } else {
+        SqlLogicTestRecordResult {
+            line,
+            kind: "query".into(),
+            status: "fail".into(),
+            expected: Some(expected_sorted),
+            actual: Some(actual_sorted),
+            error: None,
+        }
+    }
+}
+
+fn coerce_values(payload: &QueryResultPayload, types: &[ColumnType]) -> Result<Vec<String>, String> {
+    if payload.columns.len() != types.len() {
+        return Err(format!(
+            "expected {} column(s), got {}",
+            types.len(),
+            payload.columns.len()
+        ));
+    }
+    let mut values = Vec::with_capacity(payload.rows.len() * types.len());
+    for row in &payload.rows {
+        if row.len() != types.len() {
+            return Err("row column count mismatch".into());
+        }
+        for (cell, column_type) in row.iter().zip(types) {
+            values.push(coerce_cell(cell, *column_type)?);
+        }
+    }
+    Ok(values)
+}
+
+/// Coerces the expected values parsed out of the script through the same
+/// per-column formatting `coerce_values` applies to actual results, so e.g. a
+/// `R` column comparing `1.5` against a formatted `1.500` doesn't fail on
+/// presentation alone.
+fn coerce_expected(expected: &[String], types: &[ColumnType]) -> Result<Vec<String>, String> {
+    if types.is_empty() {
+        return Ok(expected.to_vec());
+    }
+    expected
+        .iter()
+        .enumerate()
+        .map(|(idx, cell)| coerce_cell(cell, types[idx % types.len()]))
+        .collect()
+}
+
+fn coerce_cell(cell: &str, column_type: ColumnType) -> Result<String, String> {
+    match column_type {
+        ColumnType::Text => Ok(if cell.is_empty() {
+            "(empty)".to_string()
+        } else {
+            cell.to_string()
+        }),
+        ColumnType::Integer => cell
+            .trim()
+            .parse::<i64>()
+            .map(|value| value.to_string())
+            .map_err(|_| format!("expected integer, got {cell:?}")),
+        ColumnType::Real => cell
+            .trim()
+            .parse::<f64>()
+            .map(|value| format!("{value:.3}"))
+            .map_err(|_| format!("expected real, got {cell:?}")),
+    }
+}
+
+fn apply_sort_mode(values: Vec<String>, row_width: usize, sort_mode: SortMode) -> Vec<String> {
+    match sort_mode {
+        SortMode::NoSort => values,
+        SortMode::ValueSort => {
+            let mut sorted = values;
+            sorted.sort();
+            sorted
+        }
+        SortMode::RowSort => {
+            if row_width == 0 {
+                return values;
+            }
+            let mut rows: Vec<Vec<String>> = values.chunks(row_width).map(|chunk| chunk.to_vec()).collect();
+            rows.sort();
+            rows.into_iter().flatten().collect()
+        }
+    }
+}
+
+fn digest(values: &[String]) -> String {
+    let joined = values.join("\n");
+    format!("{:x}", md5::compute(joined.as_bytes()))
+}
+
+fn parse_script(script: &str) -> Result<Vec<Record>, String> {
+    let lines: Vec<&str> = script.lines().collect();
+    let mut records = Vec::new();
+    let mut idx = 0;
+
+    while idx < lines.len() {
+        let line_no = idx + 1;
+        let line = lines[idx].trim();
+        if line.is_empty() || line.starts_with('#') {
+            idx += 1;
+            continue;
+        }
+
+        if line == "halt" {
+            records.push(Record::Halt { line: line_no });
+            idx += 1;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("hash-threshold ") {
+            let value = rest
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("line {line_no}: invalid hash-threshold value"))?;
+            records.push(Record::HashThreshold { value });
+            idx += 1;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("statement ") {
+            let expect_error = match rest.trim() {
+                "ok" => false,
+                "error" => true,
+                other => return Err(format!("line {line_no}: unknown statement directive {other:?}")),
+            };
+            idx += 1;
+            let (sql, next_idx) = collect_sql_lines(&lines, idx);
+            if sql.is_empty() {
+                return Err(format!("line {line_no}: statement record has no SQL"));
+            }
+            records.push(Record::Statement {
+                line: line_no,
+                expect_error,
+                sql,
+            });
+            idx = next_idx;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("query ") {
+            let mut parts = rest.split_whitespace();
+            let type_spec = parts
+                .next()
+                .ok_or_else(|| format!("line {line_no}: query record missing type string"))?;
+            let sort_token = parts
+                .next()
+                .ok_or_else(|| format!("line {line_no}: query record missing sort mode"))?;
+            let types = type_spec
+                .chars()
+                .map(ColumnType::parse)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("line {line_no}: {err}"))?;
+            let sort_mode = SortMode::parse(sort_token).map_err(|err| format!("line {line_no}: {err}"))?;
+
+            idx += 1;
+            let (sql, next_idx) = collect_until_separator(&lines, idx);
+            if sql.is_empty() {
+                return Err(format!("line {line_no}: query record has no SQL"));
+            }
+            idx = next_idx;
+            if idx >= lines.len() || lines[idx].trim() != "----" {
+                return Err(format!("line {line_no}: query record missing ---- separator"));
+            }
+            idx += 1;
+            let (expected, next_idx) = collect_sql_lines(&lines, idx);
+            let expected = expected
+                .split_whitespace()
+                .map(|value| value.to_string())
+                .collect();
+            idx = next_idx;
+
+            records.push(Record::Query {
+                line: line_no,
+                types,
+                sort_mode,
+                sql,
+                expected,
+            });
+            continue;
+        }
+
+        return Err(format!("line {line_no}: unrecognized record {line:?}"));
+    }
+
+    Ok(records)
+}
+
+fn collect_sql_lines(lines: &[&str], mut idx: usize) -> (String, usize) {
+    let mut collected = Vec::new();
+    while idx < lines.len() && !lines[idx].trim().is_empty() {
+        collected.push(lines[idx].trim());
+        idx += 1;
+    }
+    (collected.join("\n"), idx)
+}
+
+fn collect_until_separator(lines: &[&str], mut idx: usize) -> (String, usize) {
+    let mut collected = Vec::new();
+    while idx < lines.len() && lines[idx].trim() != "----" && !lines[idx].trim().is_empty() {
+        collected.push(lines[idx].trim());
+        idx += 1;
+    }
+    (collected.join("\n"), idx)
+}
@@ -0,0 +1,156 @@
+//! Live table watch subsystem, modeled on K2V's long-poll item watch.
+//!
+//! Each `(db_path, table)` pair has a monotonic version counter that's bumped
+//! whenever a DML statement touches that table. `watch_table` blocks (up to
+//! `WATCH_TIMEOUT`) until the version moves past the caller's last-seen value,
+//! and also pushes a `granite://table-changed` event so a frontend that isn't
+//! actively polling still finds out.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+const WATCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TableChangedEvent {
+    pub db_path: String,
+    pub table: String,
+    pub version: u64,
+}
+
+struct TableWatch {
+    version: Mutex<u64>,
+    signal: Condvar,
+    watcher_count: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl TableWatch {
+    fn new() -> Self {
+        TableWatch {
+            version: Mutex::new(0),
+            signal: Condvar::new(),
+            watcher_count: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+}
+
+type RegistryKey = (String, String);
+static REGISTRY: OnceLock<Mutex<HashMap<RegistryKey, Arc<TableWatch>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<HashMap<RegistryKey, Arc<TableWatch>>> {
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn entry_for(db_path: &str, table: &str) -> Arc<TableWatch> {
+    let mut registry = registry().lock().unwrap();
+    registry
+        .entry((db_path.to_string(), table.to_string()))
+        .or_insert_with(|| Arc::new(TableWatch::new()))
+        .clone()
+}
+
+/// Bumps the version for `table` and notifies anyone currently blocked in
+/// `watch_table`, as well as pushing a `granite://table-changed` event.
+pub fn bump(app_handle: &AppHandle, db_path: &str, table: &str) {
+    let entry = entry_for(db_path, table);
+    let version = {
+        let mut version = entry.version.lock().unwrap();
+        *version += 1;
+        *version
+    };
+    entry.signal.notify_all();
+    let _ = app_handle.emit(
+        "granite://table-changed",
+        TableChangedEvent {
+            db_path: db_path.to_string(),
+            table: table.to_string(),
+            version,
+        },
+    );
+}
+
+/// Blocks until `table`'s version moves past `last_seen_version`, the watch is
+/// cancelled via `unwatch_table`/`close_db`, or `WATCH_TIMEOUT` elapses —
+/// whichever comes first — and returns the version observed at that point.
+pub fn watch_table(db_path: &str, table: &str, last_seen_version: u64) -> u64 {
+    let entry = entry_for(db_path, table);
+    entry.watcher_count.fetch_add(1, Ordering::SeqCst);
+
+    let guard = entry.version.lock().unwrap();
+    let version = if *guard > last_seen_version || entry.cancelled.load(Ordering::SeqCst) {
+        *guard
+    } else {
+        let (guard, _timeout) = entry
+            .signal
+            .wait_timeout_while(guard, WATCH_TIMEOUT, |version| {
+                *version <= last_seen_version && !entry.cancelled.load(Ordering::SeqCst)
+            })
+            .unwrap();
+        *guard
+    };
+
+    entry.watcher_count.fetch_sub(1, Ordering::SeqCst);
+    version
+}
+
+/// Cancels any in-flight `watch_table` call for `(db_path, table)` so it
+/// returns promptly instead of waiting out the full timeout, then removes the
+/// entry so a later `watch_table` for the same pair starts a fresh watch
+/// instead of tripping over a `cancelled` flag that was never cleared.
+pub fn unwatch_table(db_path: &str, table: &str) {
+    let mut registry = registry().lock().unwrap();
+    if let Some(entry) = registry.remove(&(db_path.to_string(), table.to_string())) {
+        let _version = entry.version.lock().unwrap();
+        entry.cancelled.store(true, Ordering::SeqCst);
+        entry.signal.notify_all();
+    }
+}
+
+/// Tears down every watch registered against `db_path`, called when the
+/// database handle opened by `open_db` is closed.
+pub fn close_db(db_path: &str) {
+    let mut registry = registry().lock().unwrap();
+    registry.retain(|(path, _table), entry| {
+        if path == db_path {
+            let _version = entry.version.lock().unwrap();
+            entry.cancelled.store(true, Ordering::SeqCst);
+            entry.signal.notify_all();
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Best-effort extraction of the table name touched by a DML statement, used
+/// to decide which watches to bump after `exec_sql`/`exec_batch` run.
+pub fn touched_table(sql: &str) -> Option<String> {
+    let normalized = sql.trim();
+    let mut words = normalized.split_whitespace();
+    let keyword = words.next()?.to_ascii_uppercase();
+    let table = match keyword.as_str() {
+        "INSERT" => {
+            if words.next()?.to_ascii_uppercase() != "INTO" {
+                return None;
+            }
+            words.next()?
+        }
+        "UPDATE" => words.next()?,
+        "DELETE" => {
+            if words.next()?.to_ascii_uppercase() != "FROM" {
+                return None;
+            }
+            words.next()?
+        }
+        _ => return None,
+    };
+    Some(table.trim_matches(|ch: char| !ch.is_alphanumeric() && ch != '_').to_string())
+}